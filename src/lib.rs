@@ -1,13 +1,19 @@
 //! This crate provides a simple API for searching for a pattern in an array of bytes as either single-threaded or multi-threaded. It supports matching on either a single pattern or all possible patterns.
 
 use core::num;
+use memchr::{memchr_iter, memrchr_iter, Memchr};
 use rayon::{
-    prelude::{IndexedParallelIterator, ParallelIterator},
+    prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
     slice::ParallelSlice,
     ThreadPool, ThreadPoolBuilder,
 };
 use thiserror::Error;
 
+pub mod mt;
+pub mod reader;
+pub mod st;
+mod wu_manber;
+
 pub struct PatternScanner {
     bytes: Vec<u8>,
     threadpool: ThreadPool,
@@ -50,26 +56,310 @@ impl PatternScanner {
     ) -> Result<Vec<usize>, PatternScannerError> {
         let pattern_bytes = create_bytes_from_string(pattern)?;
 
-        // Scan the bytes for all matches of the pattern using the rayon crate
-        Ok(self.threadpool.install(|| {
-            bytes
-                .as_ref()
-                .par_windows(pattern_bytes.len())
-                .enumerate()
-                .filter(|(_, window)| {
-                    window
-                        .iter()
-                        .zip(pattern_bytes.iter())
-                        .all(|(byte, pattern_byte)| {
-                            pattern_byte.is_none() || Some(*byte) == *pattern_byte
-                        })
-                })
-                .map(|(i, _)| i)
-                .collect()
-        }))
+        Ok(self
+            .threadpool
+            .install(|| scan_all_parallel(bytes.as_ref(), &pattern_bytes)))
+    }
+
+    /// Scan for a unique pattern, given in an alternate [`PatternFormat`], in the
+    /// stored bytes
+    pub fn scan_format(&self, format: PatternFormat) -> Result<Option<usize>, PatternScannerError> {
+        self.scan_format_with_bytes(&self.bytes, format)
+    }
+
+    /// Scan for a unique pattern, given in an alternate [`PatternFormat`], in the
+    /// specified bytes
+    pub fn scan_format_with_bytes<T: AsRef<[u8]> + std::marker::Sync>(
+        &self,
+        bytes: T,
+        format: PatternFormat,
+    ) -> Result<Option<usize>, PatternScannerError> {
+        let results = self.scan_all_format_with_bytes(bytes, format)?;
+
+        // Check if there are multiple occurrences of the pattern
+        if results.len() > 1 {
+            return Err(PatternScannerError::NonUniquePattern);
+        }
+
+        // Return the first (and only) result, if any
+        Ok(results.first().copied())
+    }
+
+    /// Scan for all occurrences of a pattern, given in an alternate
+    /// [`PatternFormat`], in the stored bytes
+    pub fn scan_all_format(&self, format: PatternFormat) -> Result<Vec<usize>, PatternScannerError> {
+        self.scan_all_format_with_bytes(&self.bytes, format)
+    }
+
+    /// Scan for all occurrences of a pattern, given in an alternate
+    /// [`PatternFormat`], in the specified bytes
+    pub fn scan_all_format_with_bytes<T: AsRef<[u8]> + std::marker::Sync>(
+        &self,
+        bytes: T,
+        format: PatternFormat,
+    ) -> Result<Vec<usize>, PatternScannerError> {
+        let pattern_bytes = parse_pattern(format)?;
+
+        Ok(self
+            .threadpool
+            .install(|| scan_all_parallel(bytes.as_ref(), &pattern_bytes)))
+    }
+
+    /// Scan for a unique occurrence of each of several patterns in the stored bytes,
+    /// in a single pass over the bytes
+    pub fn scan_many<T: AsRef<str>>(
+        &self,
+        patterns: &[T],
+    ) -> Result<Vec<Option<usize>>, PatternScannerError> {
+        self.scan_many_with_bytes(&self.bytes, patterns)
+    }
+
+    /// Scan for a unique occurrence of each of several patterns in the specified bytes,
+    /// in a single pass over the bytes
+    pub fn scan_many_with_bytes<T: AsRef<[u8]>, U: AsRef<str>>(
+        &self,
+        bytes: T,
+        patterns: &[U],
+    ) -> Result<Vec<Option<usize>>, PatternScannerError> {
+        // Scan for all occurrences of each pattern in the bytes
+        let all_matches = self.scan_many_all_with_bytes(bytes, patterns)?;
+
+        let mut results = vec![None; patterns.len()];
+        let mut match_counts = vec![0usize; patterns.len()];
+        for pattern_match in all_matches {
+            match_counts[pattern_match.pattern_index] += 1;
+            results[pattern_match.pattern_index].get_or_insert(pattern_match.offset);
+        }
+
+        // Check if there are multiple occurrences of any pattern
+        if match_counts.iter().any(|&count| count > 1) {
+            return Err(PatternScannerError::NonUniquePattern);
+        }
+
+        Ok(results)
+    }
+
+    /// Scan for all occurrences of several patterns in the stored bytes, in a single
+    /// pass over the bytes
+    pub fn scan_many_all<T: AsRef<str>>(
+        &self,
+        patterns: &[T],
+    ) -> Result<Vec<PatternMatch>, PatternScannerError> {
+        self.scan_many_all_with_bytes(&self.bytes, patterns)
+    }
+
+    /// Scan for all occurrences of several patterns in the specified bytes, in a
+    /// single pass over the bytes, using the Wu-Manber multi-pattern algorithm
+    pub fn scan_many_all_with_bytes<T: AsRef<[u8]>, U: AsRef<str>>(
+        &self,
+        bytes: T,
+        patterns: &[U],
+    ) -> Result<Vec<PatternMatch>, PatternScannerError> {
+        let parsed_patterns = patterns
+            .iter()
+            .map(create_bytes_from_string)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(wu_manber::scan_many_all(bytes.as_ref(), &parsed_patterns)
+            .into_iter()
+            .map(|(pattern_index, offset)| PatternMatch {
+                pattern_index,
+                offset,
+            })
+            .collect())
+    }
+
+    /// Scan the stored bytes for matches of a pattern, yielding offsets lazily
+    /// instead of collecting them all up front. Well suited to very large
+    /// scans where the caller wants to bail out after the first few matches
+    pub fn iter_matches<T: AsRef<str>>(
+        &self,
+        pattern: T,
+    ) -> Result<MatchIter<'_>, PatternScannerError> {
+        self.iter_matches_with_bytes(&self.bytes, pattern)
+    }
+
+    /// Scan the specified bytes for matches of a pattern, yielding offsets
+    /// lazily instead of collecting them all up front
+    pub fn iter_matches_with_bytes<'a, T: AsRef<str>>(
+        &self,
+        bytes: &'a [u8],
+        pattern: T,
+    ) -> Result<MatchIter<'a>, PatternScannerError> {
+        let pattern_bytes = create_bytes_from_string(pattern)?;
+
+        Ok(MatchIter::new(bytes, pattern_bytes))
+    }
+
+    /// Scan the stored bytes for matches of a pattern, stopping early
+    /// according to the given [`MatchKind`] instead of always scanning to the end
+    ///
+    /// # See also
+    /// * [`PatternScanner::scan`] is equivalent to `scan_kind` with [`MatchKind::LeftmostFirst`]
+    /// * [`PatternScanner::scan_all`] is equivalent to `scan_kind` with [`MatchKind::All`]
+    pub fn scan_kind<T: AsRef<str>>(
+        &self,
+        pattern: T,
+        kind: MatchKind,
+    ) -> Result<Vec<usize>, PatternScannerError> {
+        self.scan_kind_with_bytes(&self.bytes, pattern, kind)
+    }
+
+    /// Scan the specified bytes for matches of a pattern, stopping early
+    /// according to the given [`MatchKind`] instead of always scanning to the end
+    pub fn scan_kind_with_bytes<T: AsRef<[u8]>, U: AsRef<str>>(
+        &self,
+        bytes: T,
+        pattern: U,
+        kind: MatchKind,
+    ) -> Result<Vec<usize>, PatternScannerError> {
+        let mut matches = self.iter_matches_with_bytes(bytes.as_ref(), pattern)?;
+
+        Ok(match kind {
+            MatchKind::All => matches.collect(),
+            MatchKind::First => matches.next().into_iter().collect(),
+            MatchKind::LeftmostFirst => {
+                let found: Vec<usize> = matches.by_ref().take(2).collect();
+                if found.len() > 1 {
+                    return Err(PatternScannerError::NonUniquePattern);
+                }
+                found
+            }
+        })
+    }
+
+    /// Scan the stored bytes for the last match of a pattern
+    pub fn rfind<T: AsRef<str>>(&self, pattern: T) -> Result<Option<usize>, PatternScannerError> {
+        self.rfind_with_bytes(&self.bytes, pattern)
+    }
+
+    /// Scan the specified bytes for the last match of a pattern
+    pub fn rfind_with_bytes<T: AsRef<[u8]>, U: AsRef<str>>(
+        &self,
+        bytes: T,
+        pattern: U,
+    ) -> Result<Option<usize>, PatternScannerError> {
+        let pattern_bytes = create_bytes_from_string(pattern)?;
+
+        Ok(rfind_pattern(bytes.as_ref(), &pattern_bytes))
+    }
+}
+
+/// How many matches a scan should look for before stopping, used by
+/// [`PatternScanner::scan_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Keep scanning for every match
+    All,
+    /// Stop as soon as the first match is found, without checking whether
+    /// any further matches exist
+    First,
+    /// Stop after the first match, but keep scanning just long enough to
+    /// confirm it's the only one. This is the semantics [`PatternScanner::scan`] uses
+    LeftmostFirst,
+}
+
+/// A lazy, forward iterator over the offsets of each match of a pattern,
+/// produced by [`PatternScanner::iter_matches`] and
+/// [`PatternScanner::iter_matches_with_bytes`]. Offsets are yielded in
+/// ascending order without ever materializing the full match list
+pub struct MatchIter<'a> {
+    bytes: &'a [u8],
+    pattern_bytes: Vec<PatternByte>,
+    state: MatchIterState<'a>,
+}
+
+enum MatchIterState<'a> {
+    Anchored {
+        hits: Memchr<'a>,
+        anchor_offset: usize,
+    },
+    Windows {
+        position: usize,
+    },
+    Done,
+}
+
+impl<'a> MatchIter<'a> {
+    fn new(bytes: &'a [u8], pattern_bytes: Vec<PatternByte>) -> Self {
+        let state = if pattern_bytes.is_empty() {
+            MatchIterState::Done
+        } else {
+            match select_anchor(&pattern_bytes) {
+                Some((anchor_offset, anchor_byte)) => MatchIterState::Anchored {
+                    hits: memchr_iter(anchor_byte, bytes),
+                    anchor_offset,
+                },
+                None => MatchIterState::Windows { position: 0 },
+            }
+        };
+
+        Self {
+            bytes,
+            pattern_bytes,
+            state,
+        }
     }
 }
 
+impl Iterator for MatchIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match &mut self.state {
+            MatchIterState::Anchored { hits, anchor_offset } => {
+                for hit in hits.by_ref() {
+                    if let Some(start) = hit.checked_sub(*anchor_offset) {
+                        if matches_pattern_at(self.bytes, start, &self.pattern_bytes) {
+                            return Some(start);
+                        }
+                    }
+                }
+                None
+            }
+            MatchIterState::Windows { position } => {
+                while *position + self.pattern_bytes.len() <= self.bytes.len() {
+                    let start = *position;
+                    *position += 1;
+                    if matches_pattern_at(self.bytes, start, &self.pattern_bytes) {
+                        return Some(start);
+                    }
+                }
+                None
+            }
+            MatchIterState::Done => None,
+        }
+    }
+}
+
+/// Find the last offset at which a pattern matches, scanning from the end of
+/// the bytes, used by [`PatternScanner::rfind`]
+fn rfind_pattern(bytes: &[u8], pattern_bytes: &[PatternByte]) -> Option<usize> {
+    if pattern_bytes.is_empty() {
+        return None;
+    }
+
+    match select_anchor(pattern_bytes) {
+        Some((anchor_offset, anchor_byte)) => memrchr_iter(anchor_byte, bytes)
+            .filter_map(|hit| hit.checked_sub(anchor_offset))
+            .find(|&start| matches_pattern_at(bytes, start, pattern_bytes)),
+        // Every byte in the pattern is a wildcard, so there is no anchor to
+        // search for; fall back to checking every window, from the end
+        None => (0..=bytes.len().saturating_sub(pattern_bytes.len()))
+            .rev()
+            .find(|&start| matches_pattern_at(bytes, start, pattern_bytes)),
+    }
+}
+
+/// A single match reported by [`PatternScanner::scan_many_all`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// The index of the pattern, in the slice passed to `scan_many_all`, that matched
+    pub pattern_index: usize,
+    /// The offset of the match in the scanned bytes
+    pub offset: usize,
+}
+
 pub struct PatternScannerBuilder {
     bytes: Vec<u8>,
     threadpool_builder: ThreadPoolBuilder,
@@ -119,39 +409,246 @@ pub enum PatternScannerError {
     //InvalidHeader { expected: String, found: String },
     #[error("pattern is not unique")]
     NonUniquePattern,
+    #[error("failed to read from the reader: {0}")]
+    Io(String),
+    #[error("the mask length ({mask_len}) does not match the code length ({code_len})")]
+    MaskLength { code_len: usize, mask_len: usize },
+    #[error("the mask character '{0}' is invalid (expected 'x' for a match, or '?'/'.' for a wildcard)")]
+    InvalidMaskChar(char),
     #[error("unknown pattern scanner error")]
     Unknown,
 }
 
-/// Create a vector of bytes from a pattern string
+/// A single byte position in a parsed pattern, expressed as a value/mask pair: a
+/// haystack byte matches when `(byte & mask) == (value & mask)`. A mask of
+/// `0x00` matches any byte (a full wildcard), `0xFF` requires an exact byte, and
+/// anything in between matches only the bits set in the mask (e.g. a nibble).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternByte {
+    value: u8,
+    mask: u8,
+}
+
+impl PatternByte {
+    /// A pattern byte that matches any byte
+    pub const WILDCARD: Self = Self { value: 0, mask: 0 };
+
+    /// A pattern byte that must match `value` exactly
+    pub fn exact(value: u8) -> Self {
+        Self { value, mask: 0xFF }
+    }
+
+    /// A pattern byte that matches `value` but ignores the bits cleared in `mask`
+    pub fn masked(value: u8, mask: u8) -> Self {
+        Self { value, mask }
+    }
+
+    /// Whether this pattern byte matches the given haystack byte
+    pub(crate) fn matches(self, byte: u8) -> bool {
+        (byte & self.mask) == (self.value & self.mask)
+    }
+
+    /// The byte this pattern byte requires an exact match on, or `None` if it
+    /// is a full or partial (nibble) wildcard
+    pub(crate) fn exact_value(self) -> Option<u8> {
+        (self.mask == 0xFF).then_some(self.value)
+    }
+}
+
+/// An alternate notation a pattern can be supplied in, for use with
+/// [`parse_pattern`], [`PatternScanner::scan_format`] and
+/// [`PatternScanner::scan_all_format`]
+#[derive(Debug, Clone, Copy)]
+pub enum PatternFormat<'a> {
+    /// Space-separated hex bytes, with `?`/`??` for a full-byte wildcard and
+    /// nibble-level wildcards like `4?`/`?B` for a half-byte wildcard. This is
+    /// the format parsed by [`create_bytes_from_string`] and used by default
+    /// throughout [`PatternScanner`].
+    SpaceHex(&'a str),
+    /// A contiguous run of bytes alongside an equal-length mask, where `x`
+    /// means the byte at that position must match and `?`/`.` means it's a
+    /// full wildcard (e.g. bytes `\x48\x8B\x00` with mask `"xx?"`).
+    CodeAndMask { code: &'a [u8], mask: &'a str },
+}
+
+/// Parse a pattern given in any of the supported [`PatternFormat`]s into the
+/// internal pattern-byte representation
+pub fn parse_pattern(format: PatternFormat) -> Result<Vec<PatternByte>, PatternScannerError> {
+    match format {
+        PatternFormat::SpaceHex(pattern) => create_bytes_from_string(pattern),
+        PatternFormat::CodeAndMask { code, mask } => parse_code_and_mask(code, mask),
+    }
+}
+
+/// Parse a code+mask pattern, as used by [`PatternFormat::CodeAndMask`]
+fn parse_code_and_mask(code: &[u8], mask: &str) -> Result<Vec<PatternByte>, PatternScannerError> {
+    let mask_chars: Vec<char> = mask.chars().collect();
+    if mask_chars.len() != code.len() {
+        return Err(PatternScannerError::MaskLength {
+            code_len: code.len(),
+            mask_len: mask_chars.len(),
+        });
+    }
+
+    code.iter()
+        .zip(mask_chars)
+        .map(|(&byte, mask_char)| match mask_char {
+            'x' => Ok(PatternByte::exact(byte)),
+            '?' | '.' => Ok(PatternByte::WILDCARD),
+            _ => Err(PatternScannerError::InvalidMaskChar(mask_char)),
+        })
+        .collect()
+}
+
+/// Create a vector of pattern bytes from a space-separated hex pattern string
 ///
 /// # Arguments
 /// * `pattern` - The pattern string
 ///
 /// # Returns
-/// * A vector of bytes
+/// * A vector of pattern bytes
 fn create_bytes_from_string<T: AsRef<str>>(
     pattern: T,
-) -> Result<Vec<Option<u8>>, PatternScannerError> {
+) -> Result<Vec<PatternByte>, PatternScannerError> {
     pattern
         .as_ref()
         .split_whitespace()
-        .map(|x| {
-            if x == "?" || x == "??" {
-                Ok(None)
+        .map(|token| {
+            if token == "?" || token == "??" {
+                Ok(PatternByte::WILDCARD)
             } else {
-                if x.len() != 2 {
-                    return Err(PatternScannerError::ByteLength(x.to_owned()));
-                }
-                match u8::from_str_radix(x, 16) {
-                    Ok(b) => Ok(Some(b)),
-                    Err(e) => Err(PatternScannerError::InvalidByte(e)),
-                }
+                parse_byte_token(token)
             }
         })
         .collect()
 }
 
+/// Parse a single two-character hex token, allowing either nibble to be a `?`
+/// wildcard (e.g. `4?`, `?B`), into a pattern byte
+fn parse_byte_token(token: &str) -> Result<PatternByte, PatternScannerError> {
+    if token.len() != 2 {
+        return Err(PatternScannerError::ByteLength(token.to_owned()));
+    }
+
+    let mut chars = token.chars();
+    let high = parse_hex_nibble(chars.next().unwrap())?;
+    let low = parse_hex_nibble(chars.next().unwrap())?;
+
+    let value = (high.unwrap_or(0) << 4) | low.unwrap_or(0);
+    let mask = (if high.is_some() { 0xF0 } else { 0x00 }) | (if low.is_some() { 0x0F } else { 0x00 });
+
+    Ok(PatternByte::masked(value, mask))
+}
+
+/// Parse a single hex nibble, or `None` if it is a `?` wildcard
+fn parse_hex_nibble(c: char) -> Result<Option<u8>, PatternScannerError> {
+    if c == '?' {
+        return Ok(None);
+    }
+
+    u8::from_str_radix(&c.to_string(), 16)
+        .map(Some)
+        .map_err(PatternScannerError::InvalidByte)
+}
+
+/// Relative commonality of each byte value in typical binaries, indexed by the
+/// byte value itself. Higher means more common. This is used to pick a rare
+/// "anchor" byte from a pattern so scanning can jump straight to candidate
+/// offsets with [`memchr`] instead of checking every window, in the same
+/// spirit as aho-corasick's `byte_frequencies` table.
+#[rustfmt::skip]
+const BYTE_FREQUENCIES: [u8; 256] = [
+    255, 15, 15, 15, 15, 15, 15, 15, 40, 40, 40, 40, 40, 40, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 40,
+    90, 90, 90, 90, 90, 90, 90, 90, 90, 90, 90, 90, 90, 90, 90, 90,
+    110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 90, 90, 90, 90, 90, 90,
+    90, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110,
+    110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 90, 90, 90, 90, 90,
+    90, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110,
+    110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 110, 90, 90, 90, 90, 40,
+    40, 40, 40, 150, 150, 150, 40, 40, 40, 150, 40, 150, 40, 40, 40, 40,
+    150, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40,
+    40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40,
+    40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40,
+    40, 40, 40, 150, 40, 40, 40, 40, 40, 150, 40, 40, 150, 40, 40, 40,
+    40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40,
+    40, 40, 40, 40, 40, 40, 40, 40, 150, 150, 40, 40, 40, 40, 40, 40,
+    40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 40, 200,
+];
+
+/// Pick the rarest exact (non-masked) byte in a pattern to use as a `memchr`
+/// anchor. A byte with a partial mask (e.g. a nibble wildcard) can't be used,
+/// since `memchr` can only search for one concrete byte value.
+///
+/// # Arguments
+/// * `pattern_bytes` - The parsed pattern to pick an anchor from
+///
+/// # Returns
+/// * The offset of the anchor within the pattern and its byte value, or
+///   `None` if the pattern has no fully exact byte
+pub(crate) fn select_anchor(pattern_bytes: &[PatternByte]) -> Option<(usize, u8)> {
+    pattern_bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, pattern_byte)| pattern_byte.mask == 0xFF)
+        .min_by_key(|(_, pattern_byte)| BYTE_FREQUENCIES[pattern_byte.value as usize])
+        .map(|(offset, pattern_byte)| (offset, pattern_byte.value))
+}
+
+/// Check whether a pattern matches the bytes starting at `start`
+///
+/// # Arguments
+/// * `bytes` - The bytes to check against
+/// * `start` - The offset in `bytes` to check the pattern at
+/// * `pattern_bytes` - The parsed pattern to match
+///
+/// # Returns
+/// * `true` if the pattern matches at `start`, accounting for wildcards and bounds
+pub(crate) fn matches_pattern_at(bytes: &[u8], start: usize, pattern_bytes: &[PatternByte]) -> bool {
+    match bytes.get(start..start + pattern_bytes.len()) {
+        Some(window) => window
+            .iter()
+            .zip(pattern_bytes.iter())
+            .all(|(&byte, pattern_byte)| pattern_byte.matches(byte)),
+        None => false,
+    }
+}
+
+/// Scan the bytes for all matches of the pattern using the rayon crate
+///
+/// # Arguments
+/// * `bytes` - The bytes to scan
+/// * `pattern_bytes` - The parsed pattern to scan for
+///
+/// # Returns
+/// * A vector of indices of the matches
+fn scan_all_parallel(bytes: &[u8], pattern_bytes: &[PatternByte]) -> Vec<usize> {
+    match select_anchor(pattern_bytes) {
+        // Use memchr to jump straight to candidate offsets, then verify
+        // each candidate in parallel instead of checking every window
+        Some((anchor_offset, anchor_byte)) => memchr_iter(anchor_byte, bytes)
+            .filter_map(|hit| hit.checked_sub(anchor_offset))
+            .collect::<Vec<usize>>()
+            .par_iter()
+            .filter(|&&start| matches_pattern_at(bytes, start, pattern_bytes))
+            .copied()
+            .collect(),
+        // No byte in the pattern is fully exact, so there is no anchor to
+        // search for; fall back to checking every window
+        None => bytes
+            .par_windows(pattern_bytes.len())
+            .enumerate()
+            .filter(|(_, window)| {
+                window
+                    .iter()
+                    .zip(pattern_bytes.iter())
+                    .all(|(&byte, pattern_byte)| pattern_byte.matches(byte))
+            })
+            .map(|(i, _)| i)
+            .collect(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,7 +658,11 @@ mod tests {
     fn test_create_bytes_from_string_1() {
         assert_eq!(
             create_bytes_from_string("AA BB CC").unwrap(),
-            vec![Some(0xAA), Some(0xBB), Some(0xCC)]
+            vec![
+                PatternByte::exact(0xAA),
+                PatternByte::exact(0xBB),
+                PatternByte::exact(0xCC)
+            ]
         );
     }
 
@@ -171,12 +672,12 @@ mod tests {
         assert_eq!(
             create_bytes_from_string("AA BB CC AA BB FF").unwrap(),
             vec![
-                Some(0xAA),
-                Some(0xBB),
-                Some(0xCC),
-                Some(0xAA),
-                Some(0xBB),
-                Some(0xFF)
+                PatternByte::exact(0xAA),
+                PatternByte::exact(0xBB),
+                PatternByte::exact(0xCC),
+                PatternByte::exact(0xAA),
+                PatternByte::exact(0xBB),
+                PatternByte::exact(0xFF)
             ]
         );
     }
@@ -186,7 +687,13 @@ mod tests {
     fn test_create_bytes_from_string_wildcard_1() {
         assert_eq!(
             create_bytes_from_string("AA BB ? ? CC").unwrap(),
-            vec![Some(0xAA), Some(0xBB), None, None, Some(0xCC)]
+            vec![
+                PatternByte::exact(0xAA),
+                PatternByte::exact(0xBB),
+                PatternByte::WILDCARD,
+                PatternByte::WILDCARD,
+                PatternByte::exact(0xCC)
+            ]
         );
     }
 
@@ -196,20 +703,29 @@ mod tests {
         assert_eq!(
             create_bytes_from_string("? AA BB ? ? CC ? ? ? FF").unwrap(),
             vec![
-                None,
-                Some(0xAA),
-                Some(0xBB),
-                None,
-                None,
-                Some(0xCC),
-                None,
-                None,
-                None,
-                Some(0xFF)
+                PatternByte::WILDCARD,
+                PatternByte::exact(0xAA),
+                PatternByte::exact(0xBB),
+                PatternByte::WILDCARD,
+                PatternByte::WILDCARD,
+                PatternByte::exact(0xCC),
+                PatternByte::WILDCARD,
+                PatternByte::WILDCARD,
+                PatternByte::WILDCARD,
+                PatternByte::exact(0xFF)
             ]
         );
     }
 
+    #[test]
+    // Test the create_bytes_from_string function with a nibble-level wildcard
+    fn test_create_bytes_from_string_nibble_wildcard() {
+        assert_eq!(
+            create_bytes_from_string("4? ?B").unwrap(),
+            vec![PatternByte::masked(0x40, 0xF0), PatternByte::masked(0x0B, 0x0F)]
+        );
+    }
+
     #[test]
     // Test the create_bytes_from_string function with an invalid byte "GG"
     fn test_create_bytes_from_string_error_invalid_byte() {
@@ -217,6 +733,38 @@ mod tests {
         assert!(create_bytes_from_string("AA GG").is_err());
     }
 
+    #[test]
+    // Test parse_pattern with the CodeAndMask format
+    fn test_parse_pattern_code_and_mask() {
+        assert_eq!(
+            parse_pattern(PatternFormat::CodeAndMask {
+                code: &[0x48, 0x8B, 0x00],
+                mask: "xx?",
+            })
+            .unwrap(),
+            vec![
+                PatternByte::exact(0x48),
+                PatternByte::exact(0x8B),
+                PatternByte::WILDCARD
+            ]
+        );
+    }
+
+    #[test]
+    // Test parse_pattern with a mismatched mask length
+    fn test_parse_pattern_code_and_mask_error_length() {
+        assert_eq!(
+            parse_pattern(PatternFormat::CodeAndMask {
+                code: &[0x48, 0x8B],
+                mask: "x",
+            }),
+            Err(PatternScannerError::MaskLength {
+                code_len: 2,
+                mask_len: 1
+            })
+        );
+    }
+
     #[test]
     // Test the create_bytes_from_string function with a string that contains a space between the bytes
     fn test_create_bytes_from_string_error_space() {
@@ -286,4 +834,80 @@ mod tests {
 
         assert_eq!(result, vec![600_000]);
     }
+
+    #[test]
+    fn test_iter_matches() {
+        let scanner = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09])
+            .build();
+
+        let result: Vec<usize> = scanner.iter_matches("33 35").unwrap().collect();
+
+        assert_eq!(result, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_iter_matches_stops_early() {
+        let scanner = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09])
+            .build();
+
+        let first_match = scanner.iter_matches("33 35").unwrap().next();
+
+        assert_eq!(first_match, Some(3));
+    }
+
+    #[test]
+    fn test_scan_kind_all() {
+        let result = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09])
+            .build()
+            .scan_kind("33 35", MatchKind::All)
+            .unwrap();
+
+        assert_eq!(result, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_scan_kind_first() {
+        let result = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09])
+            .build()
+            .scan_kind("33 35", MatchKind::First)
+            .unwrap();
+
+        assert_eq!(result, vec![3]);
+    }
+
+    #[test]
+    fn test_scan_kind_leftmost_first_nonunique() {
+        let result = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09])
+            .build()
+            .scan_kind("33 35", MatchKind::LeftmostFirst);
+
+        assert_eq!(result, Err(PatternScannerError::NonUniquePattern));
+    }
+
+    #[test]
+    fn test_rfind() {
+        let result = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09])
+            .build()
+            .rfind("33 35")
+            .unwrap();
+
+        assert_eq!(result, Some(5));
+    }
+
+    #[test]
+    fn test_rfind_no_match() {
+        let result = PatternScannerBuilder::builder()
+            .with_bytes([0x00, 0x01, 0x02])
+            .build()
+            .rfind("33 35")
+            .unwrap();
+
+        assert_eq!(result, None);
+    }
 }
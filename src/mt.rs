@@ -1,6 +1,7 @@
-use crate::{create_bytes_from_string, PatternScannerError};
+use crate::{create_bytes_from_string, matches_pattern_at, select_anchor, PatternScannerError};
+use memchr::memchr_iter;
 use rayon::{
-    prelude::{IndexedParallelIterator, ParallelIterator},
+    prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
     slice::ParallelSlice,
 };
 
@@ -51,7 +52,7 @@ pub fn pattern_scan(bytes: &[u8], pattern: &str) -> Result<Option<usize>, Patter
             window
                 .iter()
                 .zip(pattern_bytes.iter())
-                .all(|(byte, pattern_byte)| pattern_byte.is_none() || Some(*byte) == *pattern_byte)
+                .all(|(&byte, pattern_byte)| pattern_byte.matches(byte))
         }))
 }
 
@@ -94,22 +95,34 @@ pub fn pattern_scan_all(bytes: &[u8], pattern: &str) -> Result<Vec<usize>, Patte
     // Convert the pattern string into a vector of bytes
     let pattern_bytes = create_bytes_from_string(pattern)?;
 
-    // Scan the bytes for the pattern using the rayon crate
-    let mut pattern_matches: Vec<usize> = bytes
-        .par_windows(pattern_bytes.len())
-        .enumerate()
-        .filter_map(|(i, window)| {
-            if window
-                .iter()
-                .zip(pattern_bytes.iter())
-                .all(|(byte, pattern_byte)| pattern_byte.is_none() || Some(*byte) == *pattern_byte)
-            {
-                Some(i)
-            } else {
-                None
-            }
-        })
-        .collect();
+    // Pick the rarest fixed byte in the pattern, use memchr to find its
+    // occurrences, and verify the candidates in parallel using the rayon crate
+    let mut pattern_matches: Vec<usize> = match select_anchor(&pattern_bytes) {
+        Some((anchor_offset, anchor_byte)) => memchr_iter(anchor_byte, bytes)
+            .filter_map(|hit| hit.checked_sub(anchor_offset))
+            .collect::<Vec<usize>>()
+            .par_iter()
+            .filter(|&&start| matches_pattern_at(bytes, start, &pattern_bytes))
+            .copied()
+            .collect(),
+        // Every byte in the pattern is a wildcard, so there is no anchor to
+        // search for; fall back to checking every window
+        None => bytes
+            .par_windows(pattern_bytes.len())
+            .enumerate()
+            .filter_map(|(i, window)| {
+                if window
+                    .iter()
+                    .zip(pattern_bytes.iter())
+                    .all(|(&byte, pattern_byte)| pattern_byte.matches(byte))
+                {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    };
 
     // Sort the vector so that the indices are in order
     pattern_matches.sort();
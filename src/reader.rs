@@ -0,0 +1,208 @@
+use crate::{create_bytes_from_string, matches_pattern_at, select_anchor, PatternScannerError};
+use memchr::memchr_iter;
+use std::io::Read;
+
+/// The size, in bytes, of each chunk read from the reader
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Scan a reader for a single match of the given pattern
+///
+/// # Arguments
+/// * `reader` - The reader to scan
+/// * `pattern` - The pattern to scan for
+///
+/// # Returns
+/// * The index of the match
+///
+/// # Example
+/// ```
+/// use patternscanner::reader::scan_reader;
+///
+/// let bytes: &[u8] = &[0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x36, 0x07, 0x08, 0x09];
+/// let result = scan_reader(bytes, "33 35").unwrap();
+///
+/// assert_eq!(result, Some(3));
+/// ```
+///
+/// # Errors
+/// This function returns an error if the pattern is invalid, if the pattern
+/// matches more than once, or if reading from the reader fails
+///
+/// # Performance
+/// This function scans the reader in fixed-size chunks, so arbitrarily large
+/// readers can be scanned without loading them into memory all at once
+///
+/// # See also
+/// * [scan_reader_all](fn.scan_reader_all.html)
+pub fn scan_reader<R: Read>(reader: R, pattern: &str) -> Result<Option<usize>, PatternScannerError> {
+    let results = scan_reader_all(reader, pattern)?;
+
+    // Check if there are multiple occurrences of the pattern
+    if results.len() > 1 {
+        return Err(PatternScannerError::NonUniquePattern);
+    }
+
+    // Return the first (and only) result, if any
+    Ok(results.first().copied())
+}
+
+/// Scan a reader for all matches of the given pattern
+///
+/// # Arguments
+/// * `reader` - The reader to scan
+/// * `pattern` - The pattern to scan for
+///
+/// # Returns
+/// * A vector of indices of the matches
+///
+/// # Example
+/// ```
+/// use patternscanner::reader::scan_reader_all;
+///
+/// let bytes: &[u8] = &[0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09];
+/// let result = scan_reader_all(bytes, "33 35").unwrap();
+///
+/// assert_eq!(result, [3, 5]);
+/// ```
+///
+/// # Errors
+/// This function returns an error if the pattern is invalid or if reading from
+/// the reader fails
+///
+/// # Performance
+/// This function scans the reader in fixed-size chunks, so arbitrarily large
+/// readers can be scanned without loading them into memory all at once. A match
+/// straddling a chunk boundary is never missed: the last `pattern_len - 1` bytes
+/// of each chunk are carried over and prepended to the next one
+///
+/// # See also
+/// * [scan_reader](fn.scan_reader.html)
+pub fn scan_reader_all<R: Read>(
+    mut reader: R,
+    pattern: &str,
+) -> Result<Vec<usize>, PatternScannerError> {
+    // Convert the pattern string into a vector of bytes
+    let pattern_bytes = create_bytes_from_string(pattern)?;
+    let pattern_len = pattern_bytes.len();
+    let mut matches = Vec::new();
+
+    if pattern_len == 0 {
+        return Ok(matches);
+    }
+
+    // The buffer holds up to one chunk of fresh data plus the bytes carried over
+    // from the end of the previous chunk
+    let mut buffer = vec![0u8; CHUNK_SIZE + pattern_len - 1];
+
+    // The number of bytes at the front of `buffer` carried over from last time,
+    // and the absolute stream offset of `buffer[0]`
+    let mut carry_len = 0;
+    let mut base_offset = 0;
+
+    loop {
+        let read_len = reader
+            .read(&mut buffer[carry_len..])
+            .map_err(|error| PatternScannerError::Io(error.to_string()))?;
+        if read_len == 0 {
+            break;
+        }
+
+        let filled = carry_len + read_len;
+        let window = &buffer[..filled];
+
+        match select_anchor(&pattern_bytes) {
+            // Use memchr to jump straight to candidate offsets; matches_pattern_at
+            // rejects any candidate that doesn't yet have a full window of bytes,
+            // so it is picked up again once more data has been carried forward
+            Some((anchor_offset, anchor_byte)) => {
+                for hit in memchr_iter(anchor_byte, window) {
+                    if let Some(start) = hit.checked_sub(anchor_offset) {
+                        if matches_pattern_at(window, start, &pattern_bytes) {
+                            matches.push(base_offset + start);
+                        }
+                    }
+                }
+            }
+            // Every byte in the pattern is a wildcard, so there is no anchor to
+            // search for; fall back to checking every window
+            None => {
+                for start in 0..filled {
+                    if matches_pattern_at(window, start, &pattern_bytes) {
+                        matches.push(base_offset + start);
+                    }
+                }
+            }
+        }
+
+        // Carry the last `pattern_len - 1` bytes forward so a match straddling
+        // this chunk boundary is verified once the next chunk arrives
+        let carry = pattern_len - 1;
+        if filled > carry {
+            buffer.copy_within(filled - carry..filled, 0);
+            base_offset += filled - carry;
+            carry_len = carry;
+        } else {
+            carry_len = filled;
+        }
+    }
+
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader that only ever returns up to `chunk_size` bytes per `read`
+    /// call, regardless of the caller's buffer size, so tests can force
+    /// chunk boundaries without needing `CHUNK_SIZE`-scale input
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        chunk_size: usize,
+    }
+
+    impl std::io::Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let take = self.chunk_size.min(self.data.len()).min(buf.len());
+            buf[..take].copy_from_slice(&self.data[..take]);
+            self.data = &self.data[take..];
+            Ok(take)
+        }
+    }
+
+    #[test]
+    fn test_scan_reader_all_chunk_boundary() {
+        let bytes = [0x00, 0x01, 0x02, 0x33, 0x35, 0x33, 0x35, 0x07, 0x08, 0x09];
+
+        // 4-byte reads split the first match (at offset 3) across the
+        // boundary between the first and second `read` calls
+        let reader = ChunkedReader {
+            data: &bytes,
+            chunk_size: 4,
+        };
+
+        let result = scan_reader_all(reader, "33 35").unwrap();
+
+        assert_eq!(result, crate::st::pattern_scan_all(&bytes, "33 35").unwrap());
+    }
+
+    #[test]
+    fn test_scan_reader_all_matches_in_memory_scan() {
+        let mut bytes = vec![0u8; 5_000];
+        bytes[1_000] = 0x33;
+        bytes[1_001] = 0x35;
+        // Place a second match right where a 1-byte-per-read stream will
+        // straddle many single-byte chunk boundaries in a row
+        bytes[4_095] = 0x33;
+        bytes[4_096] = 0x35;
+
+        let reader = ChunkedReader {
+            data: &bytes,
+            chunk_size: 1,
+        };
+
+        let result = scan_reader_all(reader, "33 35").unwrap();
+
+        assert_eq!(result, crate::st::pattern_scan_all(&bytes, "33 35").unwrap());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{create_bytes_from_string, PatternScannerError};
+use crate::{create_bytes_from_string, matches_pattern_at, select_anchor, PatternScannerError};
+use memchr::memchr_iter;
 
 /// Scan the bytes for a single match of the given pattern
 ///
@@ -37,7 +38,7 @@ pub fn pattern_scan(bytes: &[u8], pattern: &str) -> Result<Option<usize>, Patter
         window
             .iter()
             .zip(pattern_bytes.iter())
-            .all(|(byte, pattern_byte)| pattern_byte.is_none() || Some(*byte) == *pattern_byte)
+            .all(|(&byte, pattern_byte)| pattern_byte.matches(byte))
     }))
 }
 
@@ -74,20 +75,29 @@ pub fn pattern_scan_all(bytes: &[u8], pattern: &str) -> Result<Vec<usize>, Patte
     // Convert the pattern string into a vector of bytes
     let pattern_bytes = create_bytes_from_string(pattern)?;
 
-    // Scan the bytes for the pattern, with the help of .windows() to scan the bytes sequentially
-    Ok(bytes
-        .windows(pattern_bytes.len())
-        .enumerate()
-        .filter_map(|(i, window)| {
-            if window
-                .iter()
-                .zip(pattern_bytes.iter())
-                .all(|(byte, pattern_byte)| pattern_byte.is_none() || Some(*byte) == *pattern_byte)
-            {
-                Some(i)
-            } else {
-                None
-            }
-        })
-        .collect())
+    // Pick the rarest fixed byte in the pattern and let memchr jump straight to
+    // its occurrences, instead of testing every window with .windows()
+    Ok(match select_anchor(&pattern_bytes) {
+        Some((anchor_offset, anchor_byte)) => memchr_iter(anchor_byte, bytes)
+            .filter_map(|hit| hit.checked_sub(anchor_offset))
+            .filter(|&start| matches_pattern_at(bytes, start, &pattern_bytes))
+            .collect(),
+        // Every byte in the pattern is a wildcard, so there is no anchor to
+        // search for; fall back to checking every window
+        None => bytes
+            .windows(pattern_bytes.len())
+            .enumerate()
+            .filter_map(|(i, window)| {
+                if window
+                    .iter()
+                    .zip(pattern_bytes.iter())
+                    .all(|(&byte, pattern_byte)| pattern_byte.matches(byte))
+                {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    })
 }
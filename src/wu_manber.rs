@@ -0,0 +1,204 @@
+use crate::{matches_pattern_at, select_anchor, PatternByte};
+use memchr::memchr_iter;
+use std::collections::HashMap;
+
+/// Scan the bytes for all occurrences of multiple patterns in a single pass using the
+/// Wu-Manber multi-pattern algorithm
+///
+/// # Arguments
+/// * `bytes` - The bytes to scan
+/// * `patterns` - The parsed patterns to scan for
+///
+/// # Returns
+/// * A vector of `(pattern_index, offset)` pairs, sorted by offset
+pub(crate) fn scan_many_all(bytes: &[u8], patterns: &[Vec<PatternByte>]) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+
+    // The shortest pattern determines the size of the sliding window every
+    // pattern is indexed over; patterns shorter than a block can't be hashed at all
+    let shortest_pattern_len = match patterns.iter().map(|pattern| pattern.len()).min() {
+        Some(len) if len > 0 => len,
+        _ => {
+            // Nothing to index; fall back to scanning every pattern independently
+            scan_patterns_by_anchor(bytes, patterns, &(0..patterns.len()).collect::<Vec<_>>(), &mut matches);
+            matches.sort_by_key(|&(pattern_index, offset)| (offset, pattern_index));
+            return matches;
+        }
+    };
+    let block_size = if shortest_pattern_len >= 3 { 3 } else { 2 };
+
+    if shortest_pattern_len < block_size {
+        scan_patterns_by_anchor(bytes, patterns, &(0..patterns.len()).collect::<Vec<_>>(), &mut matches);
+        matches.sort_by_key(|&(pattern_index, offset)| (offset, pattern_index));
+        return matches;
+    }
+
+    let default_shift = shortest_pattern_len - block_size + 1;
+    let mut shift_table: HashMap<u32, usize> = HashMap::new();
+    let mut hash_table: HashMap<u32, Vec<usize>> = HashMap::new();
+    let mut fallback_patterns = Vec::new();
+
+    for (pattern_index, pattern) in patterns.iter().enumerate() {
+        let prefix = &pattern[..shortest_pattern_len];
+        let mut all_blocks_hashable = true;
+        let mut registered_final_block = false;
+
+        for j in block_size..=shortest_pattern_len {
+            let block = &prefix[j - block_size..j];
+
+            // Blocks containing a wildcard or a partial (nibble) mask can't be
+            // hashed, so they contribute no shift and never register a
+            // verification candidate
+            let Some(hash) = hash_pattern_block(block) else {
+                all_blocks_hashable = false;
+                continue;
+            };
+
+            let candidate_shift = shortest_pattern_len - j;
+            shift_table
+                .entry(hash)
+                .and_modify(|shift| *shift = (*shift).min(candidate_shift))
+                .or_insert(candidate_shift);
+
+            if j == shortest_pattern_len && all_blocks_hashable {
+                hash_table.entry(hash).or_default().push(pattern_index);
+                registered_final_block = true;
+            }
+        }
+
+        // A pattern with an unhashable block anywhere in its prefix-of-length-m
+        // can't be found through the hash table (a missing shift entry would let
+        // the scan jump past its matches), so it must be verified by brute force
+        if !registered_final_block {
+            fallback_patterns.push(pattern_index);
+        }
+    }
+
+    let mut i = shortest_pattern_len - 1;
+    while i < bytes.len() {
+        let block = &bytes[i + 1 - block_size..=i];
+        let hash = hash_exact_block(block);
+
+        let shift = shift_table.get(&hash).copied().unwrap_or(default_shift);
+        if shift > 0 {
+            i += shift;
+            continue;
+        }
+
+        if let Some(candidates) = hash_table.get(&hash) {
+            let start = i + 1 - shortest_pattern_len;
+            for &pattern_index in candidates {
+                if matches_pattern_at(bytes, start, &patterns[pattern_index]) {
+                    matches.push((pattern_index, start));
+                }
+            }
+        }
+        i += 1;
+    }
+
+    scan_patterns_by_anchor(bytes, patterns, &fallback_patterns, &mut matches);
+
+    matches.sort_by_key(|&(pattern_index, offset)| (offset, pattern_index));
+    matches
+}
+
+/// Hash a block of haystack bytes into a lookup key
+fn hash_exact_block(block: &[u8]) -> u32 {
+    block
+        .iter()
+        .fold(0u32, |hash, &byte| (hash << 8) | byte as u32)
+}
+
+/// Hash a block of pattern bytes into a lookup key
+///
+/// # Returns
+/// * `None` if the block contains any byte that isn't fully exact (a full
+///   wildcard or a partial/nibble mask)
+fn hash_pattern_block(block: &[PatternByte]) -> Option<u32> {
+    block
+        .iter()
+        .try_fold(0u32, |hash, pattern_byte| pattern_byte.exact_value().map(|byte| (hash << 8) | byte as u32))
+}
+
+/// Scan for the given patterns independently, using the rare-byte memchr anchoring
+/// from the single-pattern scanners, for patterns that can't use the hash table
+fn scan_patterns_by_anchor(
+    bytes: &[u8],
+    patterns: &[Vec<PatternByte>],
+    pattern_indices: &[usize],
+    matches: &mut Vec<(usize, usize)>,
+) {
+    for &pattern_index in pattern_indices {
+        let pattern = &patterns[pattern_index];
+
+        match select_anchor(pattern) {
+            Some((anchor_offset, anchor_byte)) => {
+                for hit in memchr_iter(anchor_byte, bytes) {
+                    if let Some(start) = hit.checked_sub(anchor_offset) {
+                        if matches_pattern_at(bytes, start, pattern) {
+                            matches.push((pattern_index, start));
+                        }
+                    }
+                }
+            }
+            // Every byte in the pattern is a wildcard; check every window
+            None => {
+                for start in 0..=bytes.len().saturating_sub(pattern.len()) {
+                    if matches_pattern_at(bytes, start, pattern) {
+                        matches.push((pattern_index, start));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_bytes_from_string;
+
+    fn pattern(pattern: &str) -> Vec<PatternByte> {
+        create_bytes_from_string(pattern).unwrap()
+    }
+
+    #[test]
+    // Two patterns of different lengths whose indexed prefix is identical
+    // share a hash bucket; the full-pattern verification at each candidate
+    // must still tell them apart
+    fn test_scan_many_all_shared_hash_bucket() {
+        let bytes = [
+            0x41, 0x42, 0x43, 0x99, 0x41, 0x42, 0x43, 0x44, 0x45, 0x99,
+        ];
+        let patterns = [pattern("41 42 43"), pattern("41 42 43 44 45")];
+
+        let result = scan_many_all(&bytes, &patterns);
+
+        assert_eq!(result, vec![(0, 0), (0, 4), (1, 4)]);
+    }
+
+    #[test]
+    // A pattern whose final indexed block contains a wildcard can't be
+    // hashed, so it must fall back to the brute-force anchor scan while the
+    // other, fully-exact pattern is still found through the hash table
+    fn test_scan_many_all_fallback_for_non_hashable_block() {
+        let bytes = [0x41, 0x42, 0x43, 0x41, 0x99, 0x43];
+        let patterns = [pattern("41 42 43"), pattern("41 ? 43")];
+
+        let result = scan_many_all(&bytes, &patterns);
+
+        assert_eq!(result, vec![(0, 0), (1, 0), (1, 3)]);
+    }
+
+    #[test]
+    // The output is sorted by offset first, not by pattern index, even when
+    // a later pattern in the input slice matches earlier in the bytes
+    fn test_scan_many_all_sorted_by_offset() {
+        let bytes = [0x99, 0x00, 0x41];
+        let patterns = [pattern("41"), pattern("99")];
+
+        let result = scan_many_all(&bytes, &patterns);
+
+        assert_eq!(result, vec![(1, 0), (0, 2)]);
+    }
+}